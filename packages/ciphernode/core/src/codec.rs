@@ -0,0 +1,98 @@
+//! Shared, parameter-tagged byte encoding used by every (de)serializer in this crate.
+//!
+//! Every record embeds the BFV parameter shape it was produced under - degree, moduli, and
+//! plaintext modulus - not just a bare version byte. Decoding against a different `BfvParameters`
+//! fails loudly instead of silently reinterpreting bytes that happen to be the right length.
+
+use std::sync::Arc;
+
+use fhe::bfv::BfvParameters;
+
+use crate::Result;
+
+/// Bumped whenever the header or framing below changes shape.
+const CODEC_VERSION: u8 = 1;
+
+/// Prefixes `payload` with a header describing `par`'s shape.
+pub(crate) fn encode_with_header(par: &Arc<BfvParameters>, payload: &[u8]) -> Vec<u8> {
+    let moduli = par.moduli();
+    let mut out = Vec::with_capacity(1 + 4 + 1 + moduli.len() * 8 + 8 + payload.len());
+    out.push(CODEC_VERSION);
+    out.extend_from_slice(&(par.degree() as u32).to_le_bytes());
+    out.push(moduli.len() as u8);
+    for modulus in moduli {
+        out.extend_from_slice(&modulus.to_le_bytes());
+    }
+    out.extend_from_slice(&par.plaintext().to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Strips the header written by [`encode_with_header`] and checks it against `expected`,
+/// returning the remaining payload bytes.
+pub(crate) fn decode_with_header<'a>(
+    bytes: &'a [u8],
+    expected: &Arc<BfvParameters>,
+) -> Result<&'a [u8]> {
+    let (&version, rest) = bytes.split_first().ok_or("empty record")?;
+    if version != CODEC_VERSION {
+        return Err(format!("unsupported codec version {version}").into());
+    }
+
+    if rest.len() < 4 {
+        return Err("truncated record header".into());
+    }
+    let (degree_bytes, rest) = rest.split_at(4);
+    let degree = u32::from_le_bytes(degree_bytes.try_into().unwrap()) as usize;
+
+    let (&moduli_len, rest) = rest.split_first().ok_or("truncated record header")?;
+    let moduli_len = moduli_len as usize;
+    if rest.len() < moduli_len * 8 + 8 {
+        return Err("truncated record header".into());
+    }
+    let (moduli_bytes, rest) = rest.split_at(moduli_len * 8);
+    let moduli: Vec<u64> = moduli_bytes
+        .chunks_exact(8)
+        .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    let (plaintext_bytes, payload) = rest.split_at(8);
+    let plaintext = u64::from_le_bytes(plaintext_bytes.try_into().unwrap());
+
+    if degree != expected.degree() || moduli != expected.moduli() || plaintext != expected.plaintext() {
+        return Err("record was produced under different BfvParameters".into());
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::gen_params;
+
+    #[test]
+    fn round_trips_a_payload() -> Result<()> {
+        let params = gen_params();
+        let encoded = encode_with_header(&params, b"payload");
+        assert_eq!(decode_with_header(&encoded, &params)?, b"payload");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_a_mismatched_degree() -> Result<()> {
+        use fhe::bfv::BfvParametersBuilder;
+
+        let params = gen_params();
+        let encoded = encode_with_header(&params, b"payload");
+
+        let other = BfvParametersBuilder::new()
+            .set_degree(params.degree() * 2)
+            .set_plaintext_modulus(params.plaintext())
+            .set_moduli(params.moduli())
+            .build_arc()
+            .unwrap();
+
+        assert!(decode_with_header(&encoded, &other).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! Collective (threshold) decryption.
+//!
+//! A ciphertext produced under the collective public key from [`crate::keygen`] - for example the
+//! homomorphic sum of several encrypted votes - can only be opened when enough nodes cooperate.
+//! Each node holding a secret-key share contributes a [`DecryptionShare`] via `fhe::mbfv`, which
+//! adds its own "smudging" noise so the share alone statistically hides the share's secret.
+//! Aggregating the shares recovers the plaintext.
+
+use std::sync::Arc;
+
+use fhe::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, PublicKey, SecretKey};
+use fhe::mbfv::{AggregateIter, DecryptionShare};
+use fhe_traits::{DeserializeParametrized, FheDecoder, FheEncoder, FheEncrypter, Serialize};
+use rand::{CryptoRng, RngCore};
+
+use crate::codec;
+use crate::Result;
+
+/// Encrypts `values` under the collective public key. A collective public key is
+/// indistinguishable from a normal one to the encryptor, so this is plain `fhe::bfv` encryption.
+pub fn encrypt<R: RngCore + CryptoRng>(
+    params: &Arc<BfvParameters>,
+    pk: &PublicKey,
+    values: &[u64],
+    rng: &mut R,
+) -> Result<Ciphertext> {
+    let pt = Plaintext::try_encode(values, Encoding::poly(), params)?;
+    Ok(pk.try_encrypt(&pt, rng)?)
+}
+
+/// Homomorphic addition of two ciphertexts produced under the same collective key.
+pub fn add_ciphertexts(a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+    Ok(a + b)
+}
+
+/// Computes this node's decryption share. `fhe::mbfv::DecryptionShare::new` draws its own
+/// smudging noise internally, so the share alone statistically hides `secret_share`.
+pub fn partial_decrypt<R: RngCore + CryptoRng>(
+    secret_share: &SecretKey,
+    ciphertext: &Ciphertext,
+    rng: &mut R,
+) -> Result<DecryptionShare> {
+    Ok(DecryptionShare::new(secret_share, ciphertext, rng)?)
+}
+
+/// Serializes a decryption share into a parameter-tagged byte record.
+pub fn decryption_share_to_bytes(share: &DecryptionShare, par: &Arc<BfvParameters>) -> Vec<u8> {
+    codec::encode_with_header(par, &share.to_bytes())
+}
+
+/// Inverse of [`decryption_share_to_bytes`], provided the record was produced under `par`.
+pub fn decryption_share_from_bytes(bytes: &[u8], par: &Arc<BfvParameters>) -> Result<DecryptionShare> {
+    let payload = codec::decode_with_header(bytes, par)?;
+    Ok(DecryptionShare::from_bytes(payload, par)?)
+}
+
+/// Aggregates the decryption shares into the opened plaintext. Order-independent, and rejects
+/// shares produced under mismatched `BfvParameters` as part of aggregating them.
+pub fn aggregate_decryption(shares: impl IntoIterator<Item = DecryptionShare>) -> Result<Plaintext> {
+    Ok(shares.into_iter().aggregate()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keygen::{aggregate_public_key_shares, generate_public_key_share, sample_crs};
+    use crate::test_support::gen_params;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn collective_decryption_recovers_a_homomorphic_tally() -> Result<()> {
+        let params = gen_params();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        let crs = sample_crs(&params, &mut rng)?;
+
+        let sk_a = SecretKey::random(&params, &mut rng);
+        let sk_b = SecretKey::random(&params, &mut rng);
+
+        let share_a = generate_public_key_share(&sk_a, crs.clone(), &mut rng)?;
+        let share_b = generate_public_key_share(&sk_b, crs, &mut rng)?;
+        let pk = aggregate_public_key_shares(vec![share_a, share_b])?;
+
+        let vote_one = encrypt(&params, &pk, &[1], &mut rng)?;
+        let vote_two = encrypt(&params, &pk, &[1], &mut rng)?;
+        let tally = add_ciphertexts(&vote_one, &vote_two)?;
+
+        let d_a = partial_decrypt(&sk_a, &tally, &mut rng)?;
+        let d_b = partial_decrypt(&sk_b, &tally, &mut rng)?;
+
+        let decoded = aggregate_decryption(vec![d_a, d_b])?;
+        let values = Vec::<u64>::try_decode(&decoded, Encoding::poly())?;
+
+        assert_eq!(values[0], 2);
+        Ok(())
+    }
+
+    #[test]
+    fn same_master_seed_reproduces_the_same_tally_across_a_simulated_network() -> Result<()> {
+        use crate::rng::derive_rng;
+
+        let params = gen_params();
+
+        let run = || -> Result<u64> {
+            let mut crs_rng = derive_rng(b"network-seed", "crs", 0);
+            let crs = sample_crs(&params, &mut crs_rng)?;
+
+            let node_keys: Vec<SecretKey> = (0..2u64)
+                .map(|node_id| {
+                    let mut rng = derive_rng(b"network-seed", "keygen", node_id);
+                    SecretKey::random(&params, &mut rng)
+                })
+                .collect();
+
+            let shares = node_keys
+                .iter()
+                .enumerate()
+                .map(|(node_id, sk)| {
+                    let mut rng = derive_rng(b"network-seed", "keygen", node_id as u64);
+                    generate_public_key_share(sk, crs.clone(), &mut rng)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let pk = aggregate_public_key_shares(shares)?;
+
+            let mut encrypt_rng = derive_rng(b"network-seed", "encrypt", 0);
+            let ct = encrypt(&params, &pk, &[1], &mut encrypt_rng)?;
+
+            let decryption_shares = node_keys
+                .iter()
+                .enumerate()
+                .map(|(node_id, sk)| {
+                    let mut rng = derive_rng(b"network-seed", "decrypt", node_id as u64);
+                    partial_decrypt(sk, &ct, &mut rng)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let decoded = aggregate_decryption(decryption_shares)?;
+            Ok(Vec::<u64>::try_decode(&decoded, Encoding::poly())?[0])
+        };
+
+        assert_eq!(run()?, run()?);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_decryption_rejects_a_share_from_mismatched_parameters() -> Result<()> {
+        use fhe::bfv::BfvParametersBuilder;
+
+        let params = gen_params();
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let crs = sample_crs(&params, &mut rng)?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let share = generate_public_key_share(&sk, crs, &mut rng)?;
+        let pk = aggregate_public_key_shares(vec![share])?;
+        let ct = encrypt(&params, &pk, &[1], &mut rng)?;
+        let matching_share = partial_decrypt(&sk, &ct, &mut rng)?;
+
+        let other_params = BfvParametersBuilder::new()
+            .set_degree(params.degree() * 2)
+            .set_plaintext_modulus(params.plaintext())
+            .set_moduli(params.moduli())
+            .build_arc()
+            .unwrap();
+        let mut other_rng = ChaCha8Rng::seed_from_u64(2);
+        let other_crs = sample_crs(&other_params, &mut other_rng)?;
+        let other_sk = SecretKey::random(&other_params, &mut other_rng);
+        let other_share = generate_public_key_share(&other_sk, other_crs, &mut other_rng)?;
+        let other_pk = aggregate_public_key_shares(vec![other_share])?;
+        let other_ct = encrypt(&other_params, &other_pk, &[1], &mut other_rng)?;
+        let mismatched_share = partial_decrypt(&other_sk, &other_ct, &mut other_rng)?;
+
+        assert!(aggregate_decryption(vec![matching_share, mismatched_share]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn decryption_share_bytes_round_trip() -> Result<()> {
+        let params = gen_params();
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let crs = sample_crs(&params, &mut rng)?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let share = generate_public_key_share(&sk, crs, &mut rng)?;
+        let pk = aggregate_public_key_shares(vec![share])?;
+        let ct = encrypt(&params, &pk, &[1], &mut rng)?;
+        let decryption_share = partial_decrypt(&sk, &ct, &mut rng)?;
+
+        let bytes = decryption_share_to_bytes(&decryption_share, &params);
+        let reloaded = decryption_share_from_bytes(&bytes, &params)?;
+
+        assert_eq!(decryption_share.to_bytes(), reloaded.to_bytes());
+        Ok(())
+    }
+}
@@ -17,30 +17,38 @@
 //! This puts us in good stead for both changing our infrastructure as well as allowing us to be
 //! aware of what our nodes are doing.
 
-use std::future::Future;
 use fhe::bfv::SecretKey;
 use rand::{CryptoRng, RngCore};
 
+mod codec;
+mod decrypt;
+mod keygen;
+mod rng;
+mod secret;
+mod store;
+#[cfg(test)]
+mod test_support;
+
+pub use secret::{GuardedSecret, LockMode};
+pub use store::{FileKeyStore, InMemoryKeyStore, KeyStore};
+
 pub type Error = Box<dyn std::error::Error>;
 pub type Result<T> = std::result::Result<T, Error>;
 
-
-// NOTE THIS IS MOCK TO TALK CONCEPTUALLY ABOUT WHAT SHOULD BE HERE
-// Hypothetical core function depends on a save function that uses dependency injection
-// Now after writing this we know we need an async process that takes a Secret key and persists it some how.
-// Perhaps it makes sense that a std::collections::Map trait is passed in so we can store the data
-// by passing in a slate instance. 
-pub async fn generate_and_save_key<
-    R: RngCore + CryptoRng,
-    F: FnOnce(SecretKey) -> Fut,
-    Fut: Future<Output = Result<()>>,
->(
+/// Generates this node's secret-key share, guards it in memory, and hands it to `store` to
+/// persist. `store` is the [`KeyStore`] dependency this function was always going to need: some
+/// way to put a key somewhere and get it back later, independent of which backend a given
+/// deployment uses.
+pub async fn generate_and_save_key<R: RngCore + CryptoRng, S: KeyStore>(
     params: &std::sync::Arc<fhe::bfv::BfvParameters>, // pass in the params we use
-    save: F, // pass in the thing that saves an deserializes the key
+    key_id: &str, // which slot in the store this node's share lives under
+    store: &S, // pass in the thing that persists and reloads the key
+    lock_mode: LockMode, // how strictly to enforce mlock of the key while it's in RAM
     rng: &mut R, // pass in an rng so we can test this function
 ) -> Result<()> {
     let sk_share: SecretKey = SecretKey::random(params, rng);
-    save(sk_share).await?;
+    let guarded = GuardedSecret::new(secret::to_bytes(&sk_share, params), lock_mode)?;
+    store.put(key_id, guarded.as_bytes()).await?;
     Ok(())
 }
 
@@ -54,64 +62,25 @@ mod tests {
     pub type Result<T> = std::result::Result<T, Error>;
 
     use crate::*;
+    use crate::test_support::gen_params;
     use fhe::bfv::SecretKey;
-    use fhe::bfv::{self, BfvParameters};
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
-    use std::sync::Arc;
 
     #[tokio::test]
     async fn test_generate_key() -> Result<()> {
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let params = gen_params();
         let expected_sk_share = SecretKey::random(&params, &mut ChaCha8Rng::seed_from_u64(42));
+        let expected_bytes = secret::to_bytes(&expected_sk_share, &params);
 
-        let mut results: Vec<SecretKey> = vec![];
-
-        generate_and_save_key(
-            &params,
-            |sk: SecretKey| async {
-                results.push(sk);
-                Ok(())
-            },
-            &mut rng,
-        )
-        .await?;
+        let store = InMemoryKeyStore::new();
 
-        let first = results[0].clone();
+        generate_and_save_key(&params, "node-0", &store, LockMode::Permissive, &mut rng).await?;
 
-        assert!(results.len() == 1);
-        assert!(first.eq(&expected_sk_share));
+        let stored = store.get("node-0").await?.expect("key was stored");
+        assert_eq!(stored, expected_bytes);
 
         Ok(())
     }
-
-
-    
-    fn gen_params() -> Arc<BfvParameters> {
-        let moduli: Vec<u64> = vec![0x3FFFFFFF000001];
-        let num_votes: usize = 1000;
-        let degree: usize = 2048;
-        let plaintext_modulus: u64 = match num_votes {
-            1..=999 => 1009,
-            1000..=9999 => 10007,
-            10000..=99999 => 100003,
-            100000..=199999 => 200003,
-            200000..=299999 => 300007,
-            300000..=399999 => 400009,
-            400000..=499999 => 500009,
-            500000..=599999 => 600011,
-            600000..=699999 => 700001,
-            700000..=799999 => 800011,
-            800000..=899999 => 900001,
-            _ => 1032193,
-        };
-        bfv::BfvParametersBuilder::new()
-            .set_degree(degree)
-            .set_plaintext_modulus(plaintext_modulus)
-            .set_moduli(&moduli)
-            .build_arc()
-            .unwrap()
-    }
-
 }
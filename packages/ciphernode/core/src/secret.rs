@@ -0,0 +1,178 @@
+//! Guarded in-memory storage for secret key material.
+//!
+//! Anywhere a `SecretKey` (or its serialized bytes) is held in RAM it can be swapped to disk by
+//! the OS, or left behind in a freed heap page. [`GuardedSecret`] wraps such a byte buffer: on
+//! construction it `mlock`s the backing allocation to keep it out of swap, and on [`Drop`] it
+//! zeroizes the bytes before unlocking.
+//!
+//! `mlock` can fail under a restrictive `RLIMIT_MEMLOCK` (common in containers and CI), so locking
+//! is configurable: [`LockMode::Strict`] errors when locking fails, [`LockMode::Permissive`] logs
+//! a warning and proceeds without the lock. [`LockMode::from_env`] reads this from the
+//! `ENCLAVE_SECRET_LOCK_MODE` environment variable (`strict`, the default, or `permissive`), so CI
+//! and other unprivileged environments can opt out without a code change.
+
+use std::sync::Arc;
+
+use fhe::bfv::{BfvParameters, SecretKey};
+use fhe_traits::{DeserializeParametrized, Serialize};
+use zeroize::Zeroize;
+
+use crate::codec;
+use crate::Result;
+
+/// Serializes a `SecretKey` into a parameter-tagged byte record, suitable for wrapping in a
+/// [`GuardedSecret`] or persisting via a [`crate::store::KeyStore`]. `coeffs()` is private on
+/// `SecretKey`, so this wraps the library's own `fhe_traits::Serialize` encoding rather than
+/// reaching into the key's internals.
+pub fn to_bytes(sk: &SecretKey, par: &Arc<BfvParameters>) -> Vec<u8> {
+    codec::encode_with_header(par, &sk.to_bytes())
+}
+
+/// Inverse of [`to_bytes`]: reconstructs a working `SecretKey` from a record, provided it was
+/// produced under the same `par`.
+pub fn from_bytes(bytes: &[u8], par: &Arc<BfvParameters>) -> Result<SecretKey> {
+    let payload = codec::decode_with_header(bytes, par)?;
+    Ok(SecretKey::from_bytes(payload, par)?)
+}
+
+/// How to react when the OS refuses to `mlock` a secret's backing memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Fail if the memory cannot be locked.
+    Strict,
+    /// Log a warning and proceed unlocked if the memory cannot be locked.
+    Permissive,
+}
+
+impl LockMode {
+    /// Reads the mode from `ENCLAVE_SECRET_LOCK_MODE` (`strict` or `permissive`), defaulting to
+    /// [`LockMode::Strict`] if unset or unrecognized.
+    pub fn from_env() -> Self {
+        match std::env::var("ENCLAVE_SECRET_LOCK_MODE") {
+            Ok(value) if value.eq_ignore_ascii_case("permissive") => LockMode::Permissive,
+            _ => LockMode::Strict,
+        }
+    }
+}
+
+/// A byte buffer holding secret key material that is `mlock`ed while alive and zeroized on drop.
+pub struct GuardedSecret {
+    bytes: Vec<u8>,
+    locked: bool,
+}
+
+impl GuardedSecret {
+    /// Takes ownership of `bytes` and attempts to `mlock` its backing allocation.
+    pub fn new(bytes: Vec<u8>, mode: LockMode) -> Result<Self> {
+        let locked = match (Self::lock(&bytes), mode) {
+            (Ok(()), _) => true,
+            (Err(_), LockMode::Permissive) => {
+                eprintln!("warning: failed to mlock secret key material; proceeding unlocked");
+                false
+            }
+            (Err(err), LockMode::Strict) => return Err(err),
+        };
+
+        Ok(Self { bytes, locked })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[cfg(unix)]
+    fn lock(bytes: &[u8]) -> Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        // SAFETY: `bytes` outlives this call and the pointer/length describe its own allocation.
+        let rc = unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.len()) };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn lock(_bytes: &[u8]) -> Result<()> {
+        Err("mlock is only supported on unix targets".into())
+    }
+
+    #[cfg(unix)]
+    fn unlock(&self) {
+        if self.bytes.is_empty() {
+            return;
+        }
+        // SAFETY: mirrors the region locked in `lock`, called at most once from `Drop`.
+        unsafe {
+            libc::munlock(self.bytes.as_ptr().cast(), self.bytes.len());
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn unlock(&self) {}
+}
+
+impl Drop for GuardedSecret {
+    fn drop(&mut self) {
+        // Zeroize in place: `Vec::zeroize` would also clear the vec, leaving `unlock` an empty
+        // buffer and so skipping the `munlock`.
+        self.bytes.as_mut_slice().zeroize();
+        if self.locked {
+            self.unlock();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::gen_params;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn permissive_mode_succeeds_even_if_locking_is_unavailable() {
+        let guarded = GuardedSecret::new(vec![1, 2, 3], LockMode::Permissive);
+        assert!(guarded.is_ok());
+    }
+
+    #[test]
+    fn lock_mode_from_env_defaults_to_strict() {
+        std::env::remove_var("ENCLAVE_SECRET_LOCK_MODE");
+        assert_eq!(LockMode::from_env(), LockMode::Strict);
+    }
+
+    #[test]
+    fn secret_key_bytes_round_trip_into_a_working_key() -> Result<()> {
+        let params = gen_params();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let sk = SecretKey::random(&params, &mut rng);
+
+        let bytes = to_bytes(&sk, &params);
+        let reloaded = from_bytes(&bytes, &params)?;
+
+        assert!(sk.eq(&reloaded));
+        Ok(())
+    }
+
+    #[test]
+    fn from_bytes_rejects_a_record_produced_under_different_params() -> Result<()> {
+        use fhe::bfv::BfvParametersBuilder;
+
+        let params = gen_params();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let sk = SecretKey::random(&params, &mut rng);
+        let bytes = to_bytes(&sk, &params);
+
+        let other = BfvParametersBuilder::new()
+            .set_degree(params.degree() * 2)
+            .set_plaintext_modulus(params.plaintext())
+            .set_moduli(params.moduli())
+            .build_arc()
+            .unwrap();
+
+        assert!(from_bytes(&bytes, &other).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Shared test fixtures. Kept in one place so the parameter set every test builds against can't
+//! drift between modules.
+#![cfg(test)]
+
+use std::sync::Arc;
+
+use fhe::bfv::{self, BfvParameters};
+
+/// The parameter set this crate's tests exercise: a 2048-degree, single-modulus BFV instance
+/// sized for a 1000-voter tally. The plaintext modulus must be large enough to hold the tally
+/// without wrapping, so it's picked from the voter count the same way a real deployment would.
+pub(crate) fn gen_params() -> Arc<BfvParameters> {
+    let moduli: Vec<u64> = vec![0x3FFFFFFF000001];
+    let num_votes: usize = 1000;
+    let degree: usize = 2048;
+    let plaintext_modulus: u64 = match num_votes {
+        1..=999 => 1009,
+        1000..=9999 => 10007,
+        10000..=99999 => 100003,
+        100000..=199999 => 200003,
+        200000..=299999 => 300007,
+        300000..=399999 => 400009,
+        400000..=499999 => 500009,
+        500000..=599999 => 600011,
+        600000..=699999 => 700001,
+        700000..=799999 => 800011,
+        800000..=899999 => 900001,
+        _ => 1032193,
+    };
+    bfv::BfvParametersBuilder::new()
+        .set_degree(degree)
+        .set_plaintext_modulus(plaintext_modulus)
+        .set_moduli(&moduli)
+        .build_arc()
+        .unwrap()
+}